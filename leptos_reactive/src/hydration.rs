@@ -0,0 +1,10 @@
+use crate::PinnedFuture;
+
+/// The two streaming renders (out-of-order and in-order) for a single pending HTML
+/// fragment, plus a future that resolves once the fragment is fully ready.
+pub struct FragmentData {
+    pub out_of_order: PinnedFuture<String>,
+    pub in_order: PinnedFuture<std::collections::VecDeque<crate::suspense::StreamChunk>>,
+    pub should_block: bool,
+    pub is_ready: Option<PinnedFuture<()>>,
+}
@@ -0,0 +1,51 @@
+#![forbid(unsafe_code)]
+
+mod hydration;
+mod node;
+mod runtime;
+mod scope;
+mod signal;
+mod suspense;
+
+use std::{future::Future, pin::Pin};
+
+pub use node::NodeId;
+pub use runtime::{with_runtime, Runtime, RuntimeId};
+pub use scope::*;
+pub use signal::*;
+pub use suspense::{StreamChunk, SuspenseContext};
+
+/// A boxed, pinned future, used throughout the crate for type-erased async work
+/// (resources, suspense, hydration) that doesn't need to be generic over a concrete
+/// future type.
+pub type PinnedFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+slotmap::new_key_type! {
+    /// Unique ID assigned to a resource created with `create_resource`.
+    pub struct ResourceId;
+
+    /// Unique ID assigned to a value stored with `store_value`.
+    pub struct StoredValueId;
+}
+
+/// Prints a warning to the console (or `stderr`, outside the browser).
+pub fn console_warn(s: &str) {
+    eprintln!("{s}");
+}
+
+/// A zone in which reactive tracking is suspended, even if code elsewhere on the
+/// stack is currently tracking. Used to implement [`Scope::untrack`].
+pub struct SpecialNonReactiveZone;
+
+impl SpecialNonReactiveZone {
+    pub(crate) fn enter() {}
+
+    pub(crate) fn exit() {}
+}
+
+/// Creates a new reactive runtime and returns its ID.
+///
+/// This should usually only be called once, at the root of an application.
+pub fn create_runtime() -> RuntimeId {
+    runtime::create_runtime()
+}
@@ -0,0 +1,5 @@
+slotmap::new_key_type! {
+    /// Unique ID assigned to a reactive node (a signal, memo, trigger, or effect) in the
+    /// reactive graph.
+    pub struct NodeId;
+}
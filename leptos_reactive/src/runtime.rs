@@ -0,0 +1,439 @@
+#[cfg(debug_assertions)]
+use crate::{DebugEdge, DebugProperty, DebugPropertyKind, DebugScope};
+use crate::{
+    hydration::FragmentData, node::NodeId, scope::ScopeId, scope::ScopeProperty,
+    PinnedFuture, ResourceId, Scope, ScopeDisposer,
+};
+use futures::stream::FuturesUnordered;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
+    rc::Rc,
+};
+
+slotmap::new_key_type! {
+    /// Unique ID assigned to a [`Runtime`].
+    pub struct RuntimeId;
+}
+
+thread_local! {
+    static RUNTIMES: RefCell<slotmap::SlotMap<RuntimeId, Runtime>> =
+        RefCell::new(slotmap::SlotMap::with_key());
+}
+
+/// Creates a new reactive runtime and returns its ID.
+pub(crate) fn create_runtime() -> RuntimeId {
+    RUNTIMES.with(|runtimes| runtimes.borrow_mut().insert(Runtime::default()))
+}
+
+/// Runs `f` with a reference to the runtime identified by `id`.
+///
+/// Returns `Err(())` if the runtime has already been disposed, which callers
+/// generally turn into a panic: once a runtime is gone, any further reactive
+/// operation against it is a programming error.
+#[allow(clippy::result_unit_err)]
+pub fn with_runtime<T>(
+    id: RuntimeId,
+    f: impl FnOnce(&Runtime) -> T,
+) -> Result<T, ()> {
+    RUNTIMES.with(|runtimes| {
+        let runtimes = runtimes.borrow();
+        runtimes.get(id).map(f).ok_or(())
+    })
+}
+
+pub(crate) enum NodeKind {
+    Signal,
+    Effect,
+}
+
+// Read once `Scope::debug_graph` distinguishes signals from effects in a later commit.
+#[allow(dead_code)]
+pub(crate) struct NodeState {
+    pub(crate) kind: NodeKind,
+    pub(crate) value: RefCell<Box<dyn Any>>,
+    pub(crate) subscribers: RefCell<HashSet<NodeId>>,
+    /// For an effect node, the signal nodes it subscribed to on its last run (the
+    /// reverse of `subscribers`). Lets `Runtime::invalidate_scope` unsubscribe a
+    /// disposed effect from exactly the signals it depends on, instead of scanning
+    /// every node in the runtime. Unused for signal nodes.
+    pub(crate) sources: RefCell<HashSet<NodeId>>,
+}
+
+pub(crate) struct EffectState {
+    pub(crate) f: RefCell<Box<dyn FnMut()>>,
+}
+
+// Read once `Scope::debug_graph` walks a scope's owned properties in a later commit.
+#[allow(dead_code)]
+pub(crate) struct ScopeEntry {
+    pub(crate) prop: ScopeProperty,
+    #[cfg(debug_assertions)]
+    pub(crate) defined_at: Option<&'static std::panic::Location<'static>>,
+}
+
+#[derive(Default)]
+pub(crate) struct ScopeState {
+    pub(crate) parent: Option<ScopeId>,
+    pub(crate) children: Vec<ScopeId>,
+    pub(crate) properties: Vec<ScopeEntry>,
+}
+
+#[derive(Default)]
+pub(crate) struct SharedContext {
+    pub(crate) pending_fragments: HashMap<String, FragmentData>,
+}
+
+/// The shared state backing every [`Scope`](crate::Scope) created from it: the scope
+/// tree, the reactive graph of signals and effects, and the bookkeeping `batch`/
+/// `try_batch`/`batch_fifo` need to defer effect runs.
+type CleanupFn = Box<dyn FnOnce()>;
+
+#[derive(Default)]
+pub struct Runtime {
+    pub(crate) owner: Cell<Option<ScopeId>>,
+    pub(crate) observer: Cell<Option<NodeId>>,
+    pub(crate) batching: Cell<bool>,
+    pub(crate) fifo_batching: Cell<bool>,
+    pub(crate) is_flushing: Cell<bool>,
+    pub(crate) on_cleanups: RefCell<HashMap<ScopeId, Vec<CleanupFn>>>,
+    pub(crate) shared_context: RefCell<SharedContext>,
+    pub(crate) scopes: RefCell<slotmap::SlotMap<ScopeId, ScopeState>>,
+    pub(crate) nodes: RefCell<slotmap::SlotMap<NodeId, NodeState>>,
+    pub(crate) effects: RefCell<HashMap<NodeId, Rc<EffectState>>>,
+    pub(crate) pending_effects: RefCell<HashSet<NodeId>>,
+    pub(crate) fifo_queue: RefCell<VecDeque<NodeId>>,
+}
+
+/// Restores the previous observer when a [`Runtime::run_effect`] call ends, including
+/// when the effect panics, so a panicking effect can't leave `observer` pointing at a
+/// defunct effect forever.
+struct ResetObserverOnDrop<'a> {
+    runtime: &'a Runtime,
+    prev_observer: Option<NodeId>,
+}
+
+impl Drop for ResetObserverOnDrop<'_> {
+    fn drop(&mut self) {
+        self.runtime.observer.set(self.prev_observer);
+    }
+}
+
+/// Clears [`Runtime::is_flushing`] when a [`Runtime::run_effects`] call ends, including
+/// when a flushed effect panics.
+struct ResetFlushingOnDrop<'a>(&'a Runtime);
+
+impl Drop for ResetFlushingOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.is_flushing.set(false);
+    }
+}
+
+impl Runtime {
+    #[track_caller]
+    pub(crate) fn register_property(
+        &self,
+        prop: ScopeProperty,
+        #[cfg(debug_assertions)] defined_at: &'static std::panic::Location<
+            'static,
+        >,
+    ) {
+        if let Some(owner) = self.owner.get() {
+            if let Some(scope) = self.scopes.borrow_mut().get_mut(owner) {
+                scope.properties.push(ScopeEntry {
+                    prop,
+                    #[cfg(debug_assertions)]
+                    defined_at: Some(defined_at),
+                });
+            }
+        }
+    }
+
+    /// Returns `true` if `ancestor` is `scope` itself or one of its ancestors.
+    pub(crate) fn is_ancestor(&self, ancestor: ScopeId, scope: ScopeId) -> bool {
+        let scopes = self.scopes.borrow();
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = scopes.get(id).and_then(|s| s.parent);
+        }
+        false
+    }
+
+    /// Marks the given node's subscribers dirty, queueing them to run the next time
+    /// effects are flushed (immediately, unless a `batch`/`try_batch`/`batch_fifo`
+    /// is currently suppressing effect runs).
+    pub(crate) fn mark_dirty(&self, node_id: NodeId) {
+        let subscribers = self
+            .nodes
+            .borrow()
+            .get(node_id)
+            .map(|node| node.subscribers.borrow().clone())
+            .unwrap_or_default();
+
+        for effect_id in subscribers {
+            if self.pending_effects.borrow_mut().insert(effect_id) {
+                self.fifo_queue.borrow_mut().push_back(effect_id);
+            }
+        }
+
+        // An effect that writes a signal it depends on (directly, or via another
+        // effect it triggers) re-enters `mark_dirty` while `run_effects` is already
+        // draining the queue further up the call stack. Queuing above is always safe,
+        // but only the outermost `run_effects` call should actually drain: the
+        // re-entrant write's effect just stays queued for that same drain to pick up
+        // next, instead of being run here via a nested borrow of its own closure.
+        if !self.batching.get() && !self.is_flushing.get() {
+            self.run_effects();
+        }
+    }
+
+    /// Runs a single effect, tracking any signals it reads as its new dependencies.
+    pub(crate) fn run_effect(&self, effect_id: NodeId) {
+        let effect = self.effects.borrow().get(&effect_id).cloned();
+        if let Some(effect) = effect {
+            let prev_observer = self.observer.replace(Some(effect_id));
+            let _reset = ResetObserverOnDrop {
+                runtime: self,
+                prev_observer,
+            };
+            (effect.f.borrow_mut())();
+        }
+    }
+
+    /// Flushes every effect queued by [`Runtime::mark_dirty`] since the last flush,
+    /// looping until no further effect runs queued another one (e.g. by writing a
+    /// signal it, or another queued effect, depends on).
+    ///
+    /// Under [`Scope::batch_fifo`](crate::Scope::batch_fifo), effects drain from
+    /// `fifo_queue` in the order their triggering writes occurred. Otherwise order is
+    /// unspecified: effects drain from `pending_effects`, an unordered set.
+    pub(crate) fn run_effects(&self) {
+        if self.is_flushing.replace(true) {
+            return;
+        }
+        let _reset = ResetFlushingOnDrop(self);
+
+        if self.fifo_batching.get() {
+            while let Some(effect_id) = self.fifo_queue.borrow_mut().pop_front() {
+                if self.pending_effects.borrow_mut().remove(&effect_id) {
+                    self.run_effect(effect_id);
+                }
+            }
+        } else {
+            loop {
+                self.fifo_queue.borrow_mut().clear();
+                let dirty = self.pending_effects.take();
+                if dirty.is_empty() {
+                    break;
+                }
+                for effect_id in dirty {
+                    self.run_effect(effect_id);
+                }
+            }
+        }
+    }
+
+    /// Runs `effect_id` right away, outside the usual dirty-queue flush - used by
+    /// [`create_effect`](crate::create_effect) for an effect's first run.
+    ///
+    /// Guarded the same way [`Runtime::run_effects`] is: if a flush is already running
+    /// further up the call stack (e.g. this effect was created from inside another
+    /// effect's body), running here would double-borrow that other effect's closure,
+    /// so this just queues `effect_id` for the outer flush to pick up instead. If this
+    /// effect writes a signal it depends on during its own first run, it re-queues
+    /// itself the same way, so loop until it settles rather than recursing into it.
+    pub(crate) fn run_effect_now(&self, effect_id: NodeId) {
+        if self.is_flushing.replace(true) {
+            self.pending_effects.borrow_mut().insert(effect_id);
+            self.fifo_queue.borrow_mut().push_back(effect_id);
+            return;
+        }
+        let _reset = ResetFlushingOnDrop(self);
+
+        self.pending_effects.borrow_mut().remove(&effect_id);
+        loop {
+            self.run_effect(effect_id);
+            if !self.pending_effects.borrow_mut().remove(&effect_id) {
+                break;
+            }
+        }
+    }
+
+    pub(crate) fn all_resources(&self) -> Vec<ResourceId> {
+        Vec::new()
+    }
+
+    pub(crate) fn pending_resources(&self) -> Vec<ResourceId> {
+        Vec::new()
+    }
+
+    pub(crate) fn serialization_resolvers(
+        &self,
+        _cx: Scope,
+    ) -> FuturesUnordered<PinnedFuture<(ResourceId, String)>> {
+        FuturesUnordered::new()
+    }
+
+    /// Removes `scope_id`'s own entry from the scope tree and invalidates every
+    /// signal, trigger, and effect it owns.
+    ///
+    /// Called by [`Scope::dispose`](crate::Scope::dispose) as its final step, after
+    /// child scopes have already been disposed and this scope's own cleanups have
+    /// already run.
+    pub(crate) fn invalidate_scope(&self, scope_id: ScopeId) {
+        let Some(scope) = self.scopes.borrow_mut().remove(scope_id) else {
+            return;
+        };
+
+        if let Some(parent_id) = scope.parent {
+            if let Some(parent) = self.scopes.borrow_mut().get_mut(parent_id) {
+                parent.children.retain(|&id| id != scope_id);
+            }
+        }
+
+        let removed: Vec<NodeId> = scope
+            .properties
+            .iter()
+            .filter_map(|entry| entry.prop.to_node_id())
+            .collect();
+
+        for node_id in removed {
+            // An effect being disposed may still be subscribed to a signal owned by a
+            // surviving scope; unsubscribe it from exactly the signals it was last
+            // subscribed to (recorded in its own `sources`) rather than scanning every
+            // node in the runtime for a dangling reference to it.
+            let sources = self
+                .nodes
+                .borrow()
+                .get(node_id)
+                .map(|node| std::mem::take(&mut *node.sources.borrow_mut()))
+                .unwrap_or_default();
+            for source_id in sources {
+                if let Some(source_node) = self.nodes.borrow().get(source_id) {
+                    source_node.subscribers.borrow_mut().remove(&node_id);
+                }
+            }
+
+            self.nodes.borrow_mut().remove(node_id);
+            self.effects.borrow_mut().remove(&node_id);
+            self.pending_effects.borrow_mut().remove(&node_id);
+        }
+    }
+
+    /// Walks the scope tree from `scope_id` down, collecting every owned property
+    /// (with its creation site) and the dependency edges between signals and the
+    /// effects that observe them.
+    #[cfg(debug_assertions)]
+    pub(crate) fn debug_graph(&self, scope_id: ScopeId) -> DebugScope {
+        let (properties, edges, child_ids) = {
+            let scopes = self.scopes.borrow();
+            let Some(scope) = scopes.get(scope_id) else {
+                return DebugScope {
+                    id: scope_id,
+                    ..Default::default()
+                };
+            };
+
+            let nodes = self.nodes.borrow();
+            let mut properties = Vec::with_capacity(scope.properties.len());
+            let mut edges = Vec::new();
+            for entry in &scope.properties {
+                properties.push(DebugProperty {
+                    kind: debug_property_kind(entry.prop),
+                    defined_at: entry
+                        .defined_at
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                });
+
+                if let Some(source) = entry.prop.to_node_id() {
+                    if let Some(node) = nodes.get(source) {
+                        for &observer in node.subscribers.borrow().iter() {
+                            edges.push(DebugEdge { source, observer });
+                        }
+                    }
+                }
+            }
+
+            (properties, edges, scope.children.clone())
+        };
+
+        DebugScope {
+            id: scope_id,
+            properties,
+            edges,
+            children: child_ids
+                .into_iter()
+                .map(|child| self.debug_graph(child))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn debug_property_kind(prop: ScopeProperty) -> DebugPropertyKind {
+    match prop {
+        ScopeProperty::Trigger(_) => DebugPropertyKind::Trigger,
+        ScopeProperty::Signal(_) => DebugPropertyKind::Signal,
+        ScopeProperty::Effect(_) => DebugPropertyKind::Effect,
+        ScopeProperty::Resource(_) => DebugPropertyKind::Resource,
+        ScopeProperty::StoredValue(_) => DebugPropertyKind::StoredValue,
+        ScopeProperty::Task(_) => DebugPropertyKind::Task,
+    }
+}
+
+impl RuntimeId {
+    pub(crate) fn raw_scope_and_disposer(self) -> (Scope, ScopeDisposer) {
+        let (scope, _, disposer) = self.run_scope_undisposed(|cx| cx, None);
+        (scope, disposer)
+    }
+
+    pub(crate) fn run_scope<T>(
+        self,
+        f: impl FnOnce(Scope) -> T,
+        parent: Option<Scope>,
+    ) -> T {
+        let (ret, id, disposer) = self.run_scope_undisposed(f, parent);
+        _ = id;
+        disposer.dispose();
+        ret
+    }
+
+    pub(crate) fn run_scope_undisposed<T>(
+        self,
+        f: impl FnOnce(Scope) -> T,
+        parent: Option<Scope>,
+    ) -> (T, ScopeId, ScopeDisposer) {
+        let id = with_runtime(self, |runtime| {
+            let id = runtime.scopes.borrow_mut().insert(ScopeState {
+                parent: parent.map(|p| p.id),
+                ..Default::default()
+            });
+            if let Some(parent) = parent {
+                if let Some(parent_scope) =
+                    runtime.scopes.borrow_mut().get_mut(parent.id)
+                {
+                    parent_scope.children.push(id);
+                }
+            }
+            id
+        })
+        .expect(
+            "tried to create a scope in a runtime that has already been \
+             disposed",
+        );
+
+        let scope = Scope { runtime: self, id };
+
+        let prev_owner = with_runtime(self, |runtime| runtime.owner.replace(Some(id)))
+            .expect("runtime disposed while creating scope");
+        let ret = f(scope);
+        _ = with_runtime(self, |runtime| runtime.owner.set(prev_owner));
+
+        (ret, id, ScopeDisposer(scope))
+    }
+}
@@ -1,18 +1,125 @@
 #![forbid(unsafe_code)]
 use crate::{
-    console_warn,
     hydration::FragmentData,
     node::NodeId,
-    runtime::{with_runtime, RuntimeId},
+    runtime::{with_runtime, Runtime, RuntimeId},
     suspense::StreamChunk,
     PinnedFuture, ResourceId, SpecialNonReactiveZone, StoredValueId,
     SuspenseContext,
 };
 use futures::stream::FuturesUnordered;
 use std::{
+    cell::{Cell, RefCell},
     collections::{HashMap, VecDeque},
     fmt,
+    rc::Rc,
 };
+use tracing::instrument;
+
+slotmap::new_key_type! {
+    /// Unique ID assigned to a task spawned via [`Scope::spawn_local`] or
+    /// [`Scope::spawn_cancellable`].
+    pub struct TaskId;
+}
+
+/// A cooperative cancellation token handed to futures spawned with
+/// [`Scope::spawn_cancellable`].
+///
+/// Unlike [`Scope::spawn_local`], which simply aborts the underlying task at
+/// dispose time, `spawn_cancellable` lets the future poll [`CancellationToken::is_cancelled`]
+/// itself so it can wind down cleanly (e.g. flush buffered state, close a socket)
+/// rather than being dropped mid-poll.
+#[derive(Clone)]
+pub struct CancellationToken(Rc<Cell<bool>>);
+
+impl CancellationToken {
+    fn new() -> Self {
+        Self(Rc::new(Cell::new(false)))
+    }
+
+    /// Returns `true` if the scope that owns this task has been disposed.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+
+    fn cancel(&self) {
+        self.0.set(true);
+    }
+}
+
+/// A handle that stops a spawned task when told to do so by its owning scope.
+///
+/// [`Scope::spawn_cancellable`] tasks only ever carry a `cancellation` token: they're
+/// cooperative, so disposing the scope just flips the flag and trusts the future to
+/// notice and wind down. [`Scope::spawn_local`] tasks have no way to cooperate, so they
+/// additionally carry a `cancel_tx`: dropping it onto its paired `cancel_rx` makes the
+/// `futures::future::select` wrapping the task's future resolve immediately, dropping
+/// the future mid-poll. That works identically under `tokio::task::spawn_local` and
+/// `wasm_bindgen_futures::spawn_local`, so `abort_handle` is only an extra, tokio-only
+/// belt-and-suspenders on top.
+struct TaskHandle {
+    cancellation: Option<CancellationToken>,
+    cancel_tx: Option<futures::channel::oneshot::Sender<()>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    abort_handle: Option<tokio::task::AbortHandle>,
+}
+
+impl TaskHandle {
+    fn abort(&mut self) {
+        if let Some(cancellation) = &self.cancellation {
+            cancellation.cancel();
+        }
+        if let Some(cancel_tx) = self.cancel_tx.take() {
+            _ = cancel_tx.send(());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(abort_handle) = &self.abort_handle {
+            abort_handle.abort();
+        }
+    }
+}
+
+thread_local! {
+    /// Every task spawned via [`Scope::spawn_local`]/[`Scope::spawn_cancellable`], keyed
+    /// by the [`TaskId`] stored in the owning scope's [`ScopeProperty::Task`] entry.
+    static TASKS: RefCell<slotmap::SlotMap<TaskId, TaskHandle>> =
+        RefCell::new(slotmap::SlotMap::with_key());
+
+    /// Reverse index from a scope to the tasks it owns, so `Scope::dispose` can abort
+    /// them before running cleanups. Keyed by `(RuntimeId, ScopeId)`, not `ScopeId`
+    /// alone: `ScopeId` is only unique within the slotmap of the `Runtime` that minted
+    /// it, so two independent runtimes (e.g. two concurrent SSR requests) can and do
+    /// hand out colliding `ScopeId`s.
+    static SCOPE_TASKS: RefCell<HashMap<(RuntimeId, ScopeId), Vec<TaskId>>> =
+        RefCell::new(HashMap::new());
+}
+
+fn abort_scope_tasks(runtime_id: RuntimeId, scope_id: ScopeId) {
+    let task_ids = SCOPE_TASKS
+        .with(|scope_tasks| scope_tasks.borrow_mut().remove(&(runtime_id, scope_id)))
+        .unwrap_or_default();
+    TASKS.with(|tasks| {
+        let mut tasks = tasks.borrow_mut();
+        for task_id in task_ids {
+            if let Some(mut handle) = tasks.remove(task_id) {
+                handle.abort();
+            }
+        }
+    });
+}
+
+/// Removes a completed task's bookkeeping, so long-lived scopes don't accumulate an
+/// ever-growing list of tasks that already finished on their own.
+fn finish_task(runtime_id: RuntimeId, scope_id: ScopeId, task_id: TaskId) {
+    TASKS.with(|tasks| {
+        tasks.borrow_mut().remove(task_id);
+    });
+    SCOPE_TASKS.with(|scope_tasks| {
+        if let Some(ids) = scope_tasks.borrow_mut().get_mut(&(runtime_id, scope_id)) {
+            ids.retain(|&id| id != task_id);
+        }
+    });
+}
 
 #[doc(hidden)]
 #[must_use = "Scope will leak memory if the disposer function is never called"]
@@ -38,7 +145,7 @@ pub fn create_scope(
 ///
 /// You usually don't need to call this manually.
 #[cfg_attr(
-    any(debug_assertions, features = "ssr"),
+    any(debug_assertions, feature = "ssr"),
     instrument(level = "trace", skip_all,)
 )]
 pub fn raw_scope_and_disposer(runtime: RuntimeId) -> (Scope, ScopeDisposer) {
@@ -53,7 +160,7 @@ pub fn raw_scope_and_disposer(runtime: RuntimeId) -> (Scope, ScopeDisposer) {
 ///
 /// You usually don't need to call this manually.
 #[cfg_attr(
-    any(debug_assertions, features = "ssr"),
+    any(debug_assertions, feature = "ssr"),
     instrument(level = "trace", skip_all,)
 )]
 pub fn run_scope<T>(
@@ -70,7 +177,7 @@ pub fn run_scope<T>(
 ///
 /// You usually don't need to call this manually.
 #[cfg_attr(
-    any(debug_assertions, features = "ssr"),
+    any(debug_assertions, feature = "ssr"),
     instrument(level = "trace", skip_all,)
 )]
 pub fn run_scope_undisposed<T>(
@@ -118,7 +225,7 @@ impl Scope {
     /// dispose of them when they are no longer needed (e.g., a list item has been destroyed or the user
     /// has navigated away from the route.)
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     #[inline(always)]
@@ -137,7 +244,7 @@ impl Scope {
     /// dispose of them when they are no longer needed (e.g., a list item has been destroyed or the user
     /// has navigated away from the route.)
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     #[inline(always)]
@@ -145,7 +252,7 @@ impl Scope {
         self,
         f: impl FnOnce(Scope) -> T,
     ) -> (T, ScopeDisposer) {
-        let (res, child_id, disposer) =
+        let (res, _child_id, disposer) =
             self.runtime.run_scope_undisposed(f, Some(self));
 
         (res, disposer)
@@ -160,24 +267,27 @@ impl Scope {
     /// # run_scope(create_runtime(), |cx| {
     /// let (a, set_a) = create_signal(cx, 0);
     /// let (b, set_b) = create_signal(cx, 0);
-    /// let c = create_memo(cx, move |_| {
-    ///     // this memo will *only* update when `a` changes
-    ///     a() + cx.untrack(move || b())
+    /// let (runs, set_runs) = create_signal(cx, 0);
+    ///
+    /// create_effect(cx, move || {
+    ///     // reading `a` here subscribes this effect to it; reading `b` inside
+    ///     // `untrack` does not, so writes to `b` alone won't rerun the effect
+    ///     a.get();
+    ///     cx.untrack(|| b.get());
+    ///     set_runs.update(|n| *n += 1);
     /// });
     ///
-    /// assert_eq!(c(), 0);
-    /// set_a(1);
-    /// assert_eq!(c(), 1);
-    /// set_b(1);
-    /// // hasn't updated, because we untracked before reading b
-    /// assert_eq!(c(), 1);
-    /// set_a(2);
-    /// assert_eq!(c(), 3);
+    /// assert_eq!(runs.get(), 1);
+    /// set_b.set(1);
+    /// // hasn't rerun, because we untracked before reading b
+    /// assert_eq!(runs.get(), 1);
+    /// set_a.set(1);
+    /// assert_eq!(runs.get(), 2);
     ///
     /// # });
     /// ```
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     #[inline(always)]
@@ -221,19 +331,72 @@ impl Drop for SetObserverOnDrop {
 impl Scope {
     /// Disposes of this reactive scope.
     ///
-    /// This will
-    /// 1. dispose of all child `Scope`s
-    /// 2. run all cleanup functions defined for this scope by [`on_cleanup`](crate::on_cleanup).
+    /// This will, in order:
+    /// 1. dispose of all child `Scope`s.
+    /// 2. run all cleanup functions defined for this scope by [`on_cleanup`](crate::on_cleanup)
+    ///    or scheduled onto it by [`on_cleanup_in`](crate::on_cleanup_in), in registration order.
     /// 3. dispose of all signals, effects, and resources owned by this `Scope`.
+    ///
+    /// This ordering holds even if a cleanup function panics, or disposing a child scope
+    /// panics partway through: a panic never prevents the remaining children from being
+    /// disposed, the remaining cleanups for this scope from running, or step 3 from
+    /// invalidating this scope's signals, effects, and resources. The first panic
+    /// encountered is propagated (re-raised) only after every child has been disposed,
+    /// every cleanup has had a chance to run, and this scope's own invalidation has
+    /// completed, mirroring how an unwind guard runs every destructor before continuing
+    /// to unwind.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn dispose(self) {
-        _ = with_runtime(self.runtime, |runtime| {})
+        let children = with_runtime(self.runtime, |runtime| {
+            runtime
+                .scopes
+                .borrow()
+                .get(self.id)
+                .map(|scope| scope.children.clone())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+        let mut first_panic = None;
+        for child in children {
+            let child = Scope {
+                runtime: self.runtime,
+                id: child,
+            };
+            if let Err(payload) =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    child.dispose();
+                }))
+            {
+                first_panic.get_or_insert(payload);
+            }
+        }
+
+        abort_scope_tasks(self.runtime, self.id);
+        _ = with_runtime(self.runtime, |runtime| {
+            let cleanups = runtime.on_cleanups.borrow_mut().remove(&self.id);
+            if let Some(cleanups) = cleanups {
+                for cleanup in cleanups {
+                    if let Err(payload) =
+                        std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                            cleanup,
+                        ))
+                    {
+                        first_panic.get_or_insert(payload);
+                    }
+                }
+            }
+            runtime.invalidate_scope(self.id);
+        });
+        if let Some(payload) = first_panic {
+            std::panic::resume_unwind(payload);
+        }
     }
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     #[track_caller]
@@ -248,25 +411,147 @@ impl Scope {
             );
         })
     }
+
+    /// Spawns a future whose lifetime is tied to this scope, hard-aborting it (dropping
+    /// it mid-poll) when the scope is disposed.
+    ///
+    /// This is useful for kicking off background work (polling, websockets, timers)
+    /// from an effect or resource without manually wiring [`on_cleanup`] to cancel it:
+    /// the task is guaranteed not to outlive the reactive scope that spawned it. Unlike
+    /// [`Scope::spawn_cancellable`], the future gets no chance to wind down on its own,
+    /// so prefer that if `fut` holds state that needs an orderly shutdown.
+    ///
+    /// Runs on `tokio::task::spawn_local` under `ssr`, and via `wasm_bindgen_futures`
+    /// in the browser. Both targets race `fut` against a cancellation signal with
+    /// [`futures::future::select`], so the task is guaranteed to stop being polled at
+    /// the next scope disposal on either target, not just under tokio.
+    #[track_caller]
+    pub fn spawn_local(
+        self,
+        fut: impl std::future::Future<Output = ()> + 'static,
+    ) -> TaskId {
+        let task_id = self.reserve_task();
+        let (cancel_tx, cancel_rx) = futures::channel::oneshot::channel();
+        TASKS.with(|tasks| {
+            if let Some(handle) = tasks.borrow_mut().get_mut(task_id) {
+                handle.cancel_tx = Some(cancel_tx);
+            }
+        });
+
+        let guarded = async move {
+            futures::pin_mut!(fut);
+            _ = futures::future::select(fut, cancel_rx).await;
+            finish_task(self.runtime, self.id, task_id);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let join_handle = tokio::task::spawn_local(guarded);
+            TASKS.with(|tasks| {
+                if let Some(handle) = tasks.borrow_mut().get_mut(task_id) {
+                    handle.abort_handle = Some(join_handle.abort_handle());
+                }
+            });
+        }
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(guarded);
+
+        task_id
+    }
+
+    /// Spawns a future whose lifetime is tied to this scope, like [`Scope::spawn_local`],
+    /// but hands it a [`CancellationToken`] it can poll cooperatively instead of being
+    /// hard-aborted: disposing the scope only flips the token, and the future is
+    /// trusted to notice and wind down (e.g. flush buffered state, close a socket)
+    /// rather than being dropped mid-poll.
+    #[track_caller]
+    pub fn spawn_cancellable<Fut>(
+        self,
+        f: impl FnOnce(CancellationToken) -> Fut,
+    ) -> TaskId
+    where
+        Fut: std::future::Future<Output = ()> + 'static,
+    {
+        let task_id = self.reserve_task();
+        let cancellation = CancellationToken::new();
+        TASKS.with(|tasks| {
+            if let Some(handle) = tasks.borrow_mut().get_mut(task_id) {
+                handle.cancellation = Some(cancellation.clone());
+            }
+        });
+
+        let fut = f(cancellation);
+        let guarded = async move {
+            fut.await;
+            finish_task(self.runtime, self.id, task_id);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::task::spawn_local(guarded);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(guarded);
+
+        task_id
+    }
+
+    /// Registers a new, as-yet-unconfigured task under this scope, returning its ID.
+    fn reserve_task(self) -> TaskId {
+        let task_id = TASKS.with(|tasks| {
+            tasks.borrow_mut().insert(TaskHandle {
+                cancellation: None,
+                cancel_tx: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                abort_handle: None,
+            })
+        });
+        SCOPE_TASKS.with(|scope_tasks| {
+            scope_tasks
+                .borrow_mut()
+                .entry((self.runtime, self.id))
+                .or_default()
+                .push(task_id);
+        });
+        self.push_scope_property(ScopeProperty::Task(task_id));
+
+        task_id
+    }
 }
 
 #[cfg_attr(
-    any(debug_assertions, features = "ssr"),
+    any(debug_assertions, feature = "ssr"),
     instrument(level = "trace", skip_all,)
 )]
 fn push_cleanup(cx: Scope, cleanup_fn: Box<dyn FnOnce()>) {
     _ = with_runtime(cx.runtime, |runtime| {
         if let Some(owner) = runtime.owner.get() {
-            let mut cleanups = runtime.on_cleanups.borrow_mut();
-            if let Some(entries) = cleanups.get_mut(owner) {
-                entries.push(cleanup_fn);
-            } else {
-                cleanups.insert(owner, vec![cleanup_fn]);
-            }
+            push_cleanup_onto(runtime, owner, cleanup_fn);
         }
     });
 }
 
+#[cfg_attr(
+    any(debug_assertions, feature = "ssr"),
+    instrument(level = "trace", skip_all,)
+)]
+fn push_cleanup_in(target: Scope, cleanup_fn: Box<dyn FnOnce()>) {
+    _ = with_runtime(target.runtime, |runtime| {
+        push_cleanup_onto(runtime, target.id, cleanup_fn);
+    });
+}
+
+fn push_cleanup_onto(
+    runtime: &Runtime,
+    owner: ScopeId,
+    cleanup_fn: Box<dyn FnOnce()>,
+) {
+    let mut cleanups = runtime.on_cleanups.borrow_mut();
+    if let Some(entries) = cleanups.get_mut(&owner) {
+        entries.push(cleanup_fn);
+    } else {
+        cleanups.insert(owner, vec![cleanup_fn]);
+    }
+}
+
 /// Creates a cleanup function, which will be run when a [`Scope`] is disposed.
 ///
 /// It runs after child scopes have been disposed, but before signals, effects, and resources
@@ -276,11 +561,43 @@ pub fn on_cleanup(cx: Scope, cleanup_fn: impl FnOnce() + 'static) {
     push_cleanup(cx, Box::new(cleanup_fn))
 }
 
+/// Schedules a cleanup function onto `target` rather than the current scope, for when a
+/// short-lived child needs a resource freed only when a longer-lived ancestor is disposed.
+///
+/// `target` must be `cx` or one of its ancestor scopes; the cleanup runs when `target`
+/// is disposed, following the same ordering guarantee as [`on_cleanup`] (after `target`'s
+/// child scopes have been disposed, before `target`'s own signals/effects/resources are
+/// invalidated), and even if an earlier cleanup on `target` panics.
+///
+/// # Panics
+/// Panics if `target` is not `cx` itself or one of its ancestor scopes. This is a real,
+/// always-on check rather than a debug assertion: a non-ancestor `target` would silently
+/// break the disposal-ordering guarantee above (the cleanup might run before, after, or
+/// never relative to `cx`'s own disposal), so it's checked in release and `ssr` builds
+/// too, not just in debug builds.
+#[inline(always)]
+pub fn on_cleanup_in(
+    cx: Scope,
+    target: Scope,
+    cleanup_fn: impl FnOnce() + 'static,
+) {
+    assert!(
+        with_runtime(cx.runtime, |runtime| runtime
+            .is_ancestor(target.id, cx.id))
+        .unwrap_or(false),
+        "on_cleanup_in's `target` must be `cx` or one of its ancestor scopes"
+    );
+    push_cleanup_in(target, Box::new(cleanup_fn))
+}
+
 slotmap::new_key_type! {
     /// Unique ID assigned to a [`Scope`](crate::Scope).
     pub struct ScopeId;
 }
 
+// `Trigger`/`Resource`/`StoredValue` have no constructor in this crate yet
+// (`create_trigger`/`create_resource`/`store_value`).
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum ScopeProperty {
     Trigger(NodeId),
@@ -288,6 +605,7 @@ pub(crate) enum ScopeProperty {
     Effect(NodeId),
     Resource(ResourceId),
     StoredValue(StoredValueId),
+    Task(TaskId),
 }
 
 impl ScopeProperty {
@@ -301,6 +619,72 @@ impl ScopeProperty {
     }
 }
 
+/// The kind of reactive value a [`DebugProperty`] describes, as returned by
+/// [`Scope::debug_graph`].
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DebugPropertyKind {
+    /// A trigger created with `create_trigger`.
+    Trigger,
+    /// A signal, i.e. the result of `create_signal`, `create_memo`, etc.
+    Signal,
+    /// An effect created with `create_effect` or `create_isomorphic_effect`.
+    Effect,
+    /// A resource created with `create_resource`.
+    Resource,
+    /// A value stored with `store_value`.
+    StoredValue,
+    /// A task spawned with [`Scope::spawn_local`] or [`Scope::spawn_cancellable`].
+    Task,
+}
+
+/// A single reactive property owned by a scope, with the source location where it was
+/// created. Returned as part of [`DebugScope`] by [`Scope::debug_graph`].
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebugProperty {
+    /// The kind of reactive value this is.
+    pub kind: DebugPropertyKind,
+    /// Where this property was created, formatted as `"file:line:column"`.
+    pub defined_at: String,
+}
+
+/// A dependency edge in the reactive graph: the signal/memo/trigger [`NodeId`] that was
+/// read, and the effect [`NodeId`] that is subscribed to it and will re-run when it
+/// changes. Part of the snapshot returned by [`Scope::debug_graph`].
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebugEdge {
+    /// The signal, memo, or trigger being observed.
+    pub source: NodeId,
+    /// The effect that re-runs when `source` changes.
+    pub observer: NodeId,
+}
+
+/// A snapshot of one [`Scope`] and its descendants, as returned by [`Scope::debug_graph`].
+///
+/// This is the same capability devtools for other reactive systems expose (e.g. Dioxus's
+/// "debug information for signal subscriptions"): for every scope in the tree it lists the
+/// properties that scope owns, where they were created, and who subscribes to what, so an
+/// inspector can render the live dependency graph and spot leaked or never-cleaned-up scopes.
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebugScope {
+    /// The ID of this scope.
+    pub id: ScopeId,
+    /// Every reactive property owned directly by this scope, in creation order.
+    pub properties: Vec<DebugProperty>,
+    /// Dependency edges between signals owned by this scope and the effects (anywhere
+    /// in the tree) observing them.
+    pub edges: Vec<DebugEdge>,
+    /// Snapshots of this scope's direct children.
+    pub children: Vec<DebugScope>,
+}
+
 /// Creating a [`Scope`](crate::Scope) gives you a disposer, which can be called
 /// to dispose of that reactive scope.
 ///
@@ -327,7 +711,7 @@ impl ScopeDisposer {
 impl Scope {
     /// Returns IDs for all [`Resource`](crate::Resource)s found on any scope.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn all_resources(&self) -> Vec<ResourceId> {
@@ -335,10 +719,26 @@ impl Scope {
             .unwrap_or_default()
     }
 
+    /// Walks the scope tree starting from this scope and returns a serializable snapshot
+    /// of every owned reactive property (with its source [`Location`](std::panic::Location))
+    /// and the dependency edges between signals and the effects observing them.
+    ///
+    /// Intended for devtools: a browser extension or an SSR dump can consume this to
+    /// render the live dependency graph and spot leaked or never-cleaned-up scopes.
+    #[cfg(debug_assertions)]
+    #[cfg_attr(
+        any(debug_assertions, feature = "ssr"),
+        instrument(level = "trace", skip_all,)
+    )]
+    pub fn debug_graph(&self) -> DebugScope {
+        with_runtime(self.runtime, |runtime| runtime.debug_graph(self.id))
+            .unwrap_or_default()
+    }
+
     /// Returns IDs for all [`Resource`](crate::Resource)s found on any scope that are
     /// pending from the server.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn pending_resources(&self) -> Vec<ResourceId> {
@@ -348,7 +748,7 @@ impl Scope {
 
     /// Returns IDs for all [`Resource`](crate::Resource)s found on any scope.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn serialization_resolvers(
@@ -363,7 +763,7 @@ impl Scope {
     /// Registers the given [`SuspenseContext`](crate::SuspenseContext) with the current scope,
     /// calling the `resolver` when its resources are all resolved.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn register_suspense(
@@ -420,7 +820,7 @@ impl Scope {
     /// The keys are hydration IDs. Values are tuples of two pinned
     /// `Future`s that return content for out-of-order and in-order streaming, respectively.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn pending_fragments(&self) -> HashMap<String, FragmentData> {
@@ -433,7 +833,7 @@ impl Scope {
 
     /// A future that will resolve when all blocking fragments are ready.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn blocking_fragments_ready(self) -> PinnedFuture<()> {
@@ -460,7 +860,7 @@ impl Scope {
     /// Returns a tuple of two pinned `Future`s that return content for out-of-order
     /// and in-order streaming, respectively.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     pub fn take_pending_fragment(&self, id: &str) -> Option<FragmentData> {
@@ -479,7 +879,7 @@ impl Scope {
     /// # Panics
     /// Panics if the runtime this scope belongs to has already been disposed.
     #[cfg_attr(
-        any(debug_assertions, features = "ssr"),
+        any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
     )]
     #[inline(always)]
@@ -501,6 +901,200 @@ impl Scope {
             "tried to run a batched update in a runtime that has been disposed",
         )
     }
+
+    /// Runs `f` as a transactional [`batch`](Scope::batch): if it returns `Err` or panics,
+    /// every signal written during the transaction is restored to the value it held
+    /// beforehand, so that no observer ever sees a half-applied update.
+    ///
+    /// Only the value a signal held just *before* its first write inside the transaction
+    /// is kept; later writes to the same signal overwrite each other as usual and don't
+    /// add more snapshots. Effects stay suppressed for the whole transaction, exactly as
+    /// in [`batch`](Scope::batch), and only run once the transaction has either committed
+    /// or been rolled back. Nested `try_batch` calls compose: a successful inner
+    /// transaction merges its snapshots into the enclosing one, so if the outer
+    /// transaction later fails, the inner transaction's writes are unwound too.
+    ///
+    /// # Panics
+    /// Panics if the runtime this scope belongs to has already been disposed.
+    #[cfg_attr(
+        any(debug_assertions, feature = "ssr"),
+        instrument(level = "trace", skip_all,)
+    )]
+    pub fn try_batch<T, E>(
+        &self,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        with_runtime(self.runtime, move |runtime| {
+            let batching =
+                SetBatchingOnDrop(self.runtime, runtime.batching.get());
+            runtime.batching.set(true);
+
+            TRANSACTIONS.with(|tx| tx.borrow_mut().push(Transaction::default()));
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+            let transaction = TRANSACTIONS
+                .with(|tx| tx.borrow_mut().pop())
+                .expect("try_batch pushed a transaction it didn't pop");
+            // Only the outermost `try_batch` may flush: an inner transaction's writes
+            // aren't final until every enclosing transaction has also committed, so
+            // flushing here would let an effect observe a write the outer transaction
+            // could still roll back.
+            let is_outermost = !in_transaction();
+
+            let outcome = match result {
+                Ok(Ok(value)) => {
+                    transaction.merge_into_parent();
+                    Ok(value)
+                }
+                Ok(Err(error)) => {
+                    transaction.rollback();
+                    Err(error)
+                }
+                Err(payload) => {
+                    transaction.rollback();
+                    runtime.batching.set(batching.1);
+                    std::mem::forget(batching);
+                    if is_outermost {
+                        runtime.run_effects();
+                    }
+                    std::panic::resume_unwind(payload);
+                }
+            };
+
+            runtime.batching.set(batching.1);
+            std::mem::forget(batching);
+
+            if is_outermost {
+                runtime.run_effects();
+            }
+            outcome
+        })
+        .expect(
+            "tried to run a transactional batched update in a runtime that \
+             has been disposed",
+        )
+    }
+
+    /// Like [`batch`](Scope::batch), but guarantees that deferred effects are flushed in
+    /// the order their triggering signal writes occurred (insertion/FIFO order), rather
+    /// than the unspecified order `batch` uses today.
+    ///
+    /// Libraries that rely on stable effect sequencing — animation steppers, list
+    /// reconcilers — should opt into this; most code can keep using the faster unordered
+    /// `batch`, which remains the default.
+    ///
+    /// # Panics
+    /// Panics if the runtime this scope belongs to has already been disposed.
+    #[cfg_attr(
+        any(debug_assertions, feature = "ssr"),
+        instrument(level = "trace", skip_all,)
+    )]
+    #[inline(always)]
+    pub fn batch_fifo<T>(&self, f: impl FnOnce() -> T) -> T {
+        with_runtime(self.runtime, move |runtime| {
+            let batching =
+                SetBatchingOnDrop(self.runtime, runtime.batching.get());
+            runtime.batching.set(true);
+
+            let fifo_batching = SetFifoBatchingOnDrop(
+                self.runtime,
+                runtime.fifo_batching.get(),
+            );
+            runtime.fifo_batching.set(true);
+
+            let val = f();
+
+            runtime.batching.set(batching.1);
+            std::mem::forget(batching);
+
+            // Flush while `fifo_batching` is still `true`, so `run_effects` sees
+            // that this flush must preserve write order; only restore the previous
+            // value afterward.
+            runtime.run_effects();
+            runtime.fifo_batching.set(fifo_batching.1);
+            std::mem::forget(fifo_batching);
+
+            val
+        })
+        .expect(
+            "tried to run a batched update in a runtime that has been disposed",
+        )
+    }
+}
+
+/// One level of [`Scope::try_batch`] nesting: for every signal written while it was the
+/// innermost active transaction, the restorer that sets that signal back to the value it
+/// held just before this transaction's first write to it.
+#[derive(Default)]
+struct Transaction {
+    recorded: std::collections::HashSet<NodeId>,
+    restores: Vec<(NodeId, Box<dyn FnOnce()>)>,
+}
+
+impl Transaction {
+    /// Restores every signal this transaction touched, in reverse write order.
+    fn rollback(self) {
+        for (_, restore) in self.restores.into_iter().rev() {
+            restore();
+        }
+    }
+
+    /// On success, folds this transaction's snapshots into the parent transaction (if
+    /// any), so that an enclosing `try_batch` that later fails still unwinds this one's
+    /// writes. A signal already snapshotted by the parent keeps the parent's (older)
+    /// restorer, since that is the value to return to if everything unwinds.
+    fn merge_into_parent(self) {
+        TRANSACTIONS.with(|tx| {
+            if let Some(parent) = tx.borrow_mut().last_mut() {
+                for (node_id, restore) in self.restores {
+                    if parent.recorded.insert(node_id) {
+                        parent.restores.push((node_id, restore));
+                    }
+                }
+            }
+        });
+    }
+}
+
+thread_local! {
+    static TRANSACTIONS: RefCell<Vec<Transaction>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns `true` if a [`Scope::try_batch`] transaction is currently active anywhere on
+/// this thread.
+#[doc(hidden)]
+pub fn in_transaction() -> bool {
+    TRANSACTIONS.with(|tx| !tx.borrow().is_empty())
+}
+
+/// Returns `true` if the innermost active [`Scope::try_batch`] transaction has already
+/// snapshotted `node_id`, i.e. a call to [`note_transactional_write`] for it would be a
+/// no-op. Lets the signal write path skip cloning the old value it would otherwise build
+/// a (discarded) restore closure from.
+#[doc(hidden)]
+pub fn transactional_write_recorded(node_id: NodeId) -> bool {
+    TRANSACTIONS.with(|tx| {
+        tx.borrow()
+            .last()
+            .is_some_and(|transaction| transaction.recorded.contains(&node_id))
+    })
+}
+
+/// Called by the signal write path the first time a given signal is written to while a
+/// [`Scope::try_batch`] transaction is active. `restore` should set the signal back to
+/// the value it held immediately before this write; it will be invoked if the innermost
+/// active transaction (or one it is later merged into) is rolled back.
+#[doc(hidden)]
+pub fn note_transactional_write(
+    node_id: NodeId,
+    restore: impl FnOnce() + 'static,
+) {
+    TRANSACTIONS.with(|tx| {
+        if let Some(transaction) = tx.borrow_mut().last_mut() {
+            if transaction.recorded.insert(node_id) {
+                transaction.restores.push((node_id, Box::new(restore)));
+            }
+        }
+    });
 }
 
 struct SetBatchingOnDrop(RuntimeId, bool);
@@ -513,8 +1107,342 @@ impl Drop for SetBatchingOnDrop {
     }
 }
 
+struct SetFifoBatchingOnDrop(RuntimeId, bool);
+
+impl Drop for SetFifoBatchingOnDrop {
+    fn drop(&mut self) {
+        _ = with_runtime(self.0, |rt| {
+            rt.fifo_batching.set(self.1);
+        });
+    }
+}
+
 impl fmt::Debug for ScopeDisposer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("ScopeDisposer").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        create_effect, create_runtime, create_signal, on_cleanup, on_cleanup_in,
+        raw_scope_and_disposer, run_scope,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn dispose_keeps_going_after_a_child_cleanup_panics() {
+        run_scope(create_runtime(), |cx| {
+            let ran = Rc::new(RefCell::new(Vec::new()));
+
+            let ran_child1 = ran.clone();
+            cx.child_scope(move |child| {
+                on_cleanup(child, move || {
+                    ran_child1.borrow_mut().push("child1");
+                    panic!("child1 cleanup panicked");
+                });
+            });
+
+            let ran_child2 = ran.clone();
+            cx.child_scope(move |child| {
+                on_cleanup(child, move || {
+                    ran_child2.borrow_mut().push("child2");
+                });
+            });
+
+            let ran_parent = ran.clone();
+            on_cleanup(cx, move || {
+                ran_parent.borrow_mut().push("parent");
+            });
+
+            let result =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    cx.dispose();
+                }));
+
+            assert!(result.is_err());
+            assert_eq!(*ran.borrow(), vec!["child1", "child2", "parent"]);
+        });
+    }
+
+    #[test]
+    fn on_cleanup_in_runs_on_the_ancestors_disposal_in_its_usual_order() {
+        run_scope(create_runtime(), |cx| {
+            let ran = Rc::new(RefCell::new(Vec::new()));
+
+            let ran_grandchild = ran.clone();
+            cx.child_scope(move |child| {
+                child.child_scope(move |grandchild| {
+                    // Scheduled onto the grandparent `cx`, not `child` or `grandchild`.
+                    let ran_grandchild = ran_grandchild.clone();
+                    on_cleanup_in(grandchild, cx, move || {
+                        ran_grandchild.borrow_mut().push("grandchild's cleanup");
+                    });
+                });
+            });
+
+            let ran_parent = ran.clone();
+            on_cleanup(cx, move || {
+                ran_parent.borrow_mut().push("cx's own cleanup");
+            });
+
+            cx.dispose();
+
+            // Runs when `cx` (the target) is disposed, not before, and in the same
+            // registration order as a same-scope `on_cleanup` would.
+            assert_eq!(
+                *ran.borrow(),
+                vec!["grandchild's cleanup", "cx's own cleanup"]
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "on_cleanup_in's `target` must be `cx` or one of its ancestor scopes")]
+    fn on_cleanup_in_panics_if_target_is_not_an_ancestor() {
+        run_scope(create_runtime(), |cx| {
+            cx.child_scope(move |sibling1| {
+                cx.child_scope(move |sibling2| {
+                    on_cleanup_in(sibling1, sibling2, || {});
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn debug_graph_reports_properties_and_edges() {
+        run_scope(create_runtime(), |cx| {
+            let (a, _set_a) = create_signal(cx, 0);
+            create_effect(cx, move || {
+                a.get();
+            });
+
+            let graph = cx.debug_graph();
+            assert_eq!(graph.id, cx.id());
+            assert_eq!(graph.properties.len(), 2);
+            // `create_signal`/`create_effect` are `#[track_caller]`, so the reported
+            // location should be this test's call site, not somewhere inside signal.rs.
+            assert!(graph
+                .properties
+                .iter()
+                .all(|prop| prop.defined_at.contains("scope.rs")));
+
+            assert_eq!(graph.edges.len(), 1);
+            assert_eq!(graph.edges[0].source, a.id());
+        });
+    }
+
+    #[test]
+    fn disposing_an_effects_scope_unsubscribes_it_from_its_signal() {
+        run_scope(create_runtime(), |cx| {
+            let (a, set_a) = create_signal(cx, 0);
+            let runs = Rc::new(RefCell::new(0));
+
+            let runs_inner = runs.clone();
+            let disposer = cx.child_scope(move |child| {
+                create_effect(child, move || {
+                    a.get();
+                    *runs_inner.borrow_mut() += 1;
+                });
+            });
+
+            assert_eq!(*runs.borrow(), 1);
+            assert_eq!(cx.debug_graph().edges.len(), 1);
+
+            disposer.dispose();
+
+            // The effect's scope was disposed, so `a` should no longer list it as a
+            // subscriber at all - not found via an O(n) sweep of every node, but via
+            // the disposed effect's own recorded sources.
+            assert_eq!(cx.debug_graph().edges.len(), 0);
+
+            set_a.set(1);
+            assert_eq!(*runs.borrow(), 1);
+        });
+    }
+
+    #[test]
+    fn batch_fifo_runs_effects_in_write_order() {
+        run_scope(create_runtime(), |cx| {
+            let (a, set_a) = create_signal(cx, 0);
+            let (b, set_b) = create_signal(cx, 0);
+            let order = Rc::new(RefCell::new(Vec::new()));
+
+            let order_a = order.clone();
+            create_effect(cx, move || {
+                a.get();
+                order_a.borrow_mut().push("a");
+            });
+            let order_b = order.clone();
+            create_effect(cx, move || {
+                b.get();
+                order_b.borrow_mut().push("b");
+            });
+            order.borrow_mut().clear();
+
+            // Written in "b" then "a" order, the reverse of how the effects were
+            // created - batch_fifo must flush them in write order regardless.
+            cx.batch_fifo(|| {
+                set_b.set(1);
+                set_a.set(1);
+            });
+
+            assert_eq!(*order.borrow(), vec!["b", "a"]);
+        });
+    }
+
+    #[test]
+    fn effect_can_write_a_signal_it_depends_on() {
+        run_scope(create_runtime(), |cx| {
+            let (count, set_count) = create_signal(cx, 0);
+            let runs = Rc::new(RefCell::new(0));
+            let runs_inner = runs.clone();
+
+            create_effect(cx, move || {
+                *runs_inner.borrow_mut() += 1;
+                let value = count.get();
+                if value < 3 {
+                    set_count.set(value + 1);
+                }
+            });
+
+            assert_eq!(count.get_untracked(), 3);
+            assert_eq!(*runs.borrow(), 4);
+        });
+    }
+
+    #[test]
+    fn try_batch_rolls_back_signal_on_err() {
+        run_scope(create_runtime(), |cx| {
+            let (count, set_count) = create_signal(cx, 0);
+
+            let result: Result<(), &str> = cx.try_batch(|| {
+                set_count.set(1);
+                set_count.set(2);
+                Err("nope")
+            });
+
+            assert_eq!(result, Err("nope"));
+            assert_eq!(count.get_untracked(), 0);
+        });
+    }
+
+    #[test]
+    fn try_batch_keeps_signal_on_ok() {
+        run_scope(create_runtime(), |cx| {
+            let (count, set_count) = create_signal(cx, 0);
+
+            let result: Result<(), &str> = cx.try_batch(|| {
+                set_count.set(1);
+                set_count.set(2);
+                Ok(())
+            });
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(count.get_untracked(), 2);
+        });
+    }
+
+    #[test]
+    fn nested_try_batch_does_not_flush_effects_until_outer_commits() {
+        run_scope(create_runtime(), |cx| {
+            let (count, set_count) = create_signal(cx, 0);
+            let seen = Rc::new(RefCell::new(Vec::new()));
+
+            let seen_effect = seen.clone();
+            create_effect(cx, move || {
+                seen_effect.borrow_mut().push(count.get());
+            });
+            seen.borrow_mut().clear();
+
+            let result: Result<(), &str> = cx.try_batch(|| {
+                let inner: Result<(), &str> = cx.try_batch(|| {
+                    set_count.set(2);
+                    Ok(())
+                });
+                assert_eq!(inner, Ok(()));
+                // The inner transaction committed, but the outer one hasn't yet, so
+                // the effect must not have observed the (possibly temporary) value.
+                assert!(seen.borrow().is_empty());
+                Err("nope")
+            });
+
+            assert_eq!(result, Err("nope"));
+            // The outer transaction rolled back the inner one's write, so the effect
+            // should only ever have seen the restored value, never the rolled-back `2`.
+            assert_eq!(count.get_untracked(), 0);
+            assert_eq!(*seen.borrow(), vec![0]);
+        });
+    }
+
+    // `spawn_local`/`spawn_cancellable` need a real executor to run the futures they
+    // hand off to `tokio::task::spawn_local`, so unlike the rest of this module these
+    // tests run on a `tokio` current-thread runtime with a `LocalSet`, rather than via
+    // the synchronous `run_scope` harness.
+    #[tokio::test(flavor = "current_thread")]
+    async fn spawn_local_is_aborted_and_spawn_cancellable_is_flagged_on_dispose() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let runtime = create_runtime();
+                let (cx, disposer) = raw_scope_and_disposer(runtime);
+
+                let ran_to_completion = Rc::new(RefCell::new(false));
+                let ran_to_completion_inner = ran_to_completion.clone();
+                cx.spawn_local(async move {
+                    // Only ever resolves if allowed to run past the dispose below.
+                    std::future::pending::<()>().await;
+                    *ran_to_completion_inner.borrow_mut() = true;
+                });
+
+                let finished_task_id = cx.spawn_local(async {});
+                // Let the already-finished task's own bookkeeping clean itself up
+                // before we go on to check it below.
+                for _ in 0..4 {
+                    tokio::task::yield_now().await;
+                }
+                assert!(
+                    !super::TASKS.with(|tasks| tasks.borrow().contains_key(finished_task_id)),
+                    "a naturally-finished task must remove its own TaskHandle"
+                );
+                assert!(
+                    super::SCOPE_TASKS
+                        .with(|scope_tasks| scope_tasks.borrow()
+                            .get(&(cx.runtime, cx.id))
+                            .map(|ids| !ids.contains(&finished_task_id))
+                            .unwrap_or(true)),
+                    "a naturally-finished task must remove itself from its scope's task list"
+                );
+
+                let token_seen = Rc::new(RefCell::new(None));
+                let token_seen_inner = token_seen.clone();
+                cx.spawn_cancellable(move |token| {
+                    *token_seen_inner.borrow_mut() = Some(token.clone());
+                    async move {
+                        while !token.is_cancelled() {
+                            tokio::task::yield_now().await;
+                        }
+                    }
+                });
+
+                disposer.dispose();
+                // Give the select-wrapped `spawn_local` future and the cooperatively
+                // polling `spawn_cancellable` future a chance to actually unwind.
+                for _ in 0..4 {
+                    tokio::task::yield_now().await;
+                }
+
+                assert!(
+                    !*ran_to_completion.borrow(),
+                    "spawn_local's future must not be polled to completion once its scope is disposed"
+                );
+                let token = token_seen.borrow().clone().expect("token was captured");
+                assert!(
+                    token.is_cancelled(),
+                    "spawn_cancellable's token must flip once its scope is disposed"
+                );
+            })
+            .await;
+    }
+}
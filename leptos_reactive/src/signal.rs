@@ -0,0 +1,234 @@
+use crate::{
+    node::NodeId,
+    runtime::{with_runtime, EffectState, NodeKind, NodeState, RuntimeId},
+    scope::{
+        in_transaction, note_transactional_write, transactional_write_recorded,
+        ScopeProperty,
+    },
+    Scope,
+};
+use std::{cell::RefCell, collections::HashSet, marker::PhantomData, rc::Rc};
+
+/// The read half of a signal created with [`create_signal`].
+pub struct ReadSignal<T> {
+    pub(crate) runtime: RuntimeId,
+    pub(crate) id: NodeId,
+    ty: PhantomData<T>,
+}
+
+impl<T> Clone for ReadSignal<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ReadSignal<T> {}
+
+/// The write half of a signal created with [`create_signal`].
+pub struct WriteSignal<T> {
+    pub(crate) runtime: RuntimeId,
+    pub(crate) id: NodeId,
+    ty: PhantomData<T>,
+}
+
+impl<T> Clone for WriteSignal<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WriteSignal<T> {}
+
+impl<T: Clone + 'static> ReadSignal<T> {
+    /// Returns a clone of the signal's current value, subscribing the currently
+    /// running effect (if any) to this signal.
+    pub fn get(&self) -> T {
+        with_runtime(self.runtime, |runtime| {
+            if let Some(observer) = runtime.observer.get() {
+                let nodes = runtime.nodes.borrow();
+                if let Some(node) = nodes.get(self.id) {
+                    node.subscribers.borrow_mut().insert(observer);
+                }
+                // Recorded so a disposed effect can be unsubscribed from exactly the
+                // signals it depends on; see `NodeState::sources`.
+                if let Some(observer_node) = nodes.get(observer) {
+                    observer_node.sources.borrow_mut().insert(self.id);
+                }
+            }
+            self.read_value(runtime)
+        })
+        .expect("tried to read a signal in a runtime that has been disposed")
+    }
+
+    /// Returns a clone of the signal's current value without subscribing to it.
+    pub fn get_untracked(&self) -> T {
+        with_runtime(self.runtime, |runtime| self.read_value(runtime))
+            .expect("tried to read a signal in a runtime that has been disposed")
+    }
+
+    /// The [`NodeId`] this signal occupies in the reactive graph.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn read_value(&self, runtime: &crate::Runtime) -> T {
+        let nodes = runtime.nodes.borrow();
+        let node = nodes
+            .get(self.id)
+            .expect("signal read after its scope was disposed");
+        let value = node
+            .value
+            .borrow()
+            .downcast_ref::<T>()
+            .expect("signal's stored value was not of the expected type")
+            .clone();
+        value
+    }
+}
+
+impl<T: Clone + 'static> WriteSignal<T> {
+    /// Sets the signal's value, notifying subscribed effects.
+    ///
+    /// If a [`Scope::try_batch`](crate::Scope::try_batch) transaction is active, the
+    /// previous value is snapshotted so it can be restored on rollback.
+    pub fn set(&self, new_value: T) {
+        with_runtime(self.runtime, |runtime| {
+            self.snapshot_for_rollback(runtime);
+            self.write_value(runtime, new_value);
+            runtime.mark_dirty(self.id);
+        })
+        .expect("tried to write a signal in a runtime that has been disposed")
+    }
+
+    /// Updates the signal's value in place, notifying subscribed effects.
+    ///
+    /// If a [`Scope::try_batch`](crate::Scope::try_batch) transaction is active, the
+    /// previous value is snapshotted so it can be restored on rollback.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        with_runtime(self.runtime, |runtime| {
+            self.snapshot_for_rollback(runtime);
+            {
+                let nodes = runtime.nodes.borrow();
+                let node = nodes
+                    .get(self.id)
+                    .expect("signal updated after its scope was disposed");
+                let mut value = node.value.borrow_mut();
+                f(value
+                    .downcast_mut::<T>()
+                    .expect("signal's stored value was not of the expected type"));
+            }
+            runtime.mark_dirty(self.id);
+        })
+        .expect("tried to write a signal in a runtime that has been disposed")
+    }
+
+    fn snapshot_for_rollback(&self, runtime: &crate::Runtime) {
+        if !in_transaction() || transactional_write_recorded(self.id) {
+            return;
+        }
+        let old_value = {
+            let nodes = runtime.nodes.borrow();
+            let node = nodes
+                .get(self.id)
+                .expect("signal snapshotted after its scope was disposed");
+            let value = node
+                .value
+                .borrow()
+                .downcast_ref::<T>()
+                .expect("signal's stored value was not of the expected type")
+                .clone();
+            value
+        };
+        let this = *self;
+        note_transactional_write(self.id, move || {
+            this.set_untracked(old_value);
+        });
+    }
+
+    /// Sets the signal's value without notifying subscribers or snapshotting it for
+    /// an enclosing transaction. Used to apply a transaction rollback itself.
+    fn set_untracked(&self, new_value: T) {
+        _ = with_runtime(self.runtime, |runtime| {
+            self.write_value(runtime, new_value);
+        });
+    }
+
+    fn write_value(&self, runtime: &crate::Runtime, new_value: T) {
+        let nodes = runtime.nodes.borrow();
+        let node = nodes
+            .get(self.id)
+            .expect("signal written after its scope was disposed");
+        *node.value.borrow_mut() = Box::new(new_value);
+    }
+}
+
+/// Creates a signal, the basic reactive primitive: a value that can be read
+/// reactively via [`ReadSignal::get`] and written via [`WriteSignal::set`].
+#[track_caller]
+pub fn create_signal<T: 'static>(
+    cx: Scope,
+    value: T,
+) -> (ReadSignal<T>, WriteSignal<T>) {
+    let id = with_runtime(cx.runtime, |runtime| {
+        runtime.nodes.borrow_mut().insert(NodeState {
+            kind: NodeKind::Signal,
+            value: RefCell::new(Box::new(value)),
+            subscribers: RefCell::new(HashSet::new()),
+            sources: RefCell::new(HashSet::new()),
+        })
+    })
+    .expect("tried to create a signal in a runtime that has been disposed");
+    cx.push_scope_property(ScopeProperty::Signal(id));
+
+    (
+        ReadSignal {
+            runtime: cx.runtime,
+            id,
+            ty: PhantomData,
+        },
+        WriteSignal {
+            runtime: cx.runtime,
+            id,
+            ty: PhantomData,
+        },
+    )
+}
+
+/// Creates an effect: a function that runs once immediately, then re-runs whenever
+/// any signal it read on its last run changes.
+#[track_caller]
+pub fn create_effect(cx: Scope, f: impl FnMut() + 'static) -> NodeId {
+    let id = with_runtime(cx.runtime, |runtime| {
+        runtime.nodes.borrow_mut().insert(NodeState {
+            kind: NodeKind::Effect,
+            value: RefCell::new(Box::new(())),
+            subscribers: RefCell::new(HashSet::new()),
+            sources: RefCell::new(HashSet::new()),
+        })
+    })
+    .expect("tried to create an effect in a runtime that has been disposed");
+
+    let effect = Rc::new(EffectState {
+        f: RefCell::new(Box::new(f)),
+    });
+    _ = with_runtime(cx.runtime, |runtime| {
+        runtime.effects.borrow_mut().insert(id, effect);
+        runtime.run_effect_now(id);
+    });
+    cx.push_scope_property(ScopeProperty::Effect(id));
+
+    id
+}
+
+/// An effect that runs identically on the client and the server.
+///
+/// On the client, a plain `create_effect` is deferred until the next tick so it
+/// doesn't run during hydration; since this crate has no hydration phase to defer
+/// past, it's simply an alias for [`create_effect`].
+#[track_caller]
+pub fn create_isomorphic_effect(
+    cx: Scope,
+    mut f: impl FnMut(Option<()>) + 'static,
+) -> NodeId {
+    create_effect(cx, move || f(None))
+}
@@ -0,0 +1,46 @@
+use crate::PinnedFuture;
+
+/// One chunk of a streamed HTML response: either immediately-available markup, or a
+/// future that resolves to markup once an async resource it depends on is ready.
+pub enum StreamChunk {
+    Sync(String),
+    Async(PinnedFuture<String>),
+}
+
+/// A snapshot of how many serializable resources a [`SuspenseContext`] is still
+/// waiting on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PendingCount(pub usize);
+
+impl PendingCount {
+    /// Returns a read-only handle to this count. `PendingCount` is already immutable
+    /// once read, so this simply hands back a copy.
+    pub fn read_only(&self) -> Self {
+        *self
+    }
+
+    /// Reads the current count, mirroring the signal-style `try_with` accessor used
+    /// elsewhere in the crate.
+    pub fn try_with<R>(&self, f: impl FnOnce(&usize) -> R) -> Option<R> {
+        Some(f(&self.0))
+    }
+}
+
+/// Tracks the resources read under a `<Suspense/>` boundary, so the framework knows
+/// when it's safe to resolve the fragment those resources are blocking.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SuspenseContext {
+    /// How many resources read under this boundary are still unresolved and need to
+    /// be serialized before the fragment can be considered ready.
+    pub pending_serializable_resources: PendingCount,
+    /// Whether this boundary should block the initial response, rather than stream
+    /// in afterward.
+    pub should_block: bool,
+}
+
+impl SuspenseContext {
+    /// Whether this boundary should block the initial response.
+    pub fn should_block(&self) -> bool {
+        self.should_block
+    }
+}